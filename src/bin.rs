@@ -30,7 +30,7 @@ pub fn main() {
     println!("Jacobian of f(x,y) = [x^2 * y , x + y] at ({}, {}) is {:?}", 1.0, 2.0, f3_result);
 
     // API for reverse mode
-    let tape = rdiff::Tape::new();
+    let tape: rdiff::Tape<f64> = rdiff::Tape::new();
     let x = tape.var(1.0);
     let y = tape.var(1.0);
     let z = -2.0 * x + x * x * x * y + 2.0 * y;
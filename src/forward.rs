@@ -6,31 +6,33 @@ use num_traits::{Float, Zero};
 
 extern crate nalgebra as na;
 
-/// A scalar dual number type
+/// A scalar dual number type. `T` is the component type, which is normally
+/// `f64` but can itself be a `DualScalar` to nest dual numbers and recover
+/// higher-order derivatives (see `hessian`).
 #[derive(Clone, Copy)]
-pub struct DualScalar {
-    pub v: f64,
-    pub dv: f64,
+pub struct DualScalar<T = f64> {
+    pub v: T,
+    pub dv: T,
 }
 
 // Traits
-impl PartialEq for DualScalar {
+impl<T: PartialEq> PartialEq for DualScalar<T> {
     fn eq(&self, other: &Self) -> bool {
         self.v == other.v
     }
 }
 
-impl Debug for DualScalar {
+impl<T: Debug> Debug for DualScalar<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "(v, dv) = ({:?}, {:?})", self.v, self.dv)
     }
 }
 
-impl Zero for DualScalar {
+impl<T: Zero + Copy> Zero for DualScalar<T> {
     fn zero() -> Self {
         DualScalar {
-            v: 0.0,
-            dv: 0.0,
+            v: T::zero(),
+            dv: T::zero(),
         }
     }
 
@@ -40,15 +42,127 @@ impl Zero for DualScalar {
 }
 
 // Other functions
-impl DualScalar {
+impl<T: Copy> DualScalar<T> {
     // Get derivative from dual number
-    pub fn deriv(&self) -> f64 {
-        self.dv.clone()
+    pub fn deriv(&self) -> T {
+        self.dv
+    }
+}
+
+// Elementary/transcendental functions need a `Float` component, so they
+// exist for `DualScalar<f32>`/`DualScalar<f64>` but not for nested
+// `DualScalar<DualScalar<f64>>`, which supports only the arithmetic ops below.
+impl<T: Float> DualScalar<T> {
+    /// Square root: d/dv sqrt(v) = 1 / (2 * sqrt(v))
+    pub fn sqrt(&self) -> Self {
+        let v = self.v.sqrt();
+        DualScalar {
+            v,
+            dv: self.dv / (v + v),
+        }
+    }
+
+    /// Exponential: d/dv exp(v) = exp(v)
+    pub fn exp(&self) -> Self {
+        let v = self.v.exp();
+        DualScalar {
+            v,
+            dv: self.dv * v,
+        }
+    }
+
+    /// Natural log: d/dv ln(v) = 1 / v
+    pub fn ln(&self) -> Self {
+        DualScalar {
+            v: self.v.ln(),
+            dv: self.dv / self.v,
+        }
+    }
+
+    /// Log with arbitrary base: d/dv log_b(v) = 1 / (v * ln(b))
+    pub fn log(&self, base: T) -> Self {
+        DualScalar {
+            v: self.v.log(base),
+            dv: self.dv / (self.v * base.ln()),
+        }
+    }
+
+    /// Power with a real exponent: d/dv v^p = p * v^(p-1)
+    pub fn powf(&self, p: T) -> Self {
+        DualScalar {
+            v: self.v.powf(p),
+            dv: self.dv * p * self.v.powf(p - T::one()),
+        }
+    }
+
+    /// Power with an integer exponent: d/dv v^n = n * v^(n-1)
+    pub fn powi(&self, n: i32) -> Self {
+        DualScalar {
+            v: self.v.powi(n),
+            dv: self.dv * T::from(n).unwrap() * self.v.powi(n - 1),
+        }
+    }
+
+    /// Absolute value: d/dv |v| = sign(v)
+    pub fn abs(&self) -> Self {
+        DualScalar {
+            v: self.v.abs(),
+            dv: self.dv * self.v.signum(),
+        }
+    }
+
+    /// Sine: d/dv sin(v) = cos(v)
+    pub fn sin(&self) -> Self {
+        DualScalar {
+            v: self.v.sin(),
+            dv: self.dv * self.v.cos(),
+        }
+    }
+
+    /// Cosine: d/dv cos(v) = -sin(v)
+    pub fn cos(&self) -> Self {
+        DualScalar {
+            v: self.v.cos(),
+            dv: -self.dv * self.v.sin(),
+        }
+    }
+
+    /// Tangent: d/dv tan(v) = 1 / cos(v)^2
+    pub fn tan(&self) -> Self {
+        DualScalar {
+            v: self.v.tan(),
+            dv: self.dv / (self.v.cos() * self.v.cos()),
+        }
+    }
+
+    /// Hyperbolic sine: d/dv sinh(v) = cosh(v)
+    pub fn sinh(&self) -> Self {
+        DualScalar {
+            v: self.v.sinh(),
+            dv: self.dv * self.v.cosh(),
+        }
+    }
+
+    /// Hyperbolic cosine: d/dv cosh(v) = sinh(v)
+    pub fn cosh(&self) -> Self {
+        DualScalar {
+            v: self.v.cosh(),
+            dv: self.dv * self.v.sinh(),
+        }
+    }
+
+    /// Hyperbolic tangent: d/dv tanh(v) = 1 - tanh(v)^2
+    pub fn tanh(&self) -> Self {
+        let v = self.v.tanh();
+        DualScalar {
+            v,
+            dv: self.dv * (T::one() - v * v),
+        }
     }
 }
 
 // Addition Rules
-impl Add for DualScalar {
+impl<T: Add<Output = T> + Copy> Add for DualScalar<T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -59,7 +173,7 @@ impl Add for DualScalar {
     }
 }
 
-impl AddAssign for DualScalar {
+impl<T: Add<Output = T> + Copy> AddAssign for DualScalar<T> {
     fn add_assign(&mut self, rhs: Self) {
         *self = Self {
             v: self.v + rhs.v,
@@ -69,7 +183,7 @@ impl AddAssign for DualScalar {
 }
 
 // Subtraction Rules
-impl Sub for DualScalar {
+impl<T: Sub<Output = T> + Copy> Sub for DualScalar<T> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
@@ -81,7 +195,7 @@ impl Sub for DualScalar {
 }
 
 // Multiplication Rules
-impl Mul for DualScalar {
+impl<T: Mul<Output = T> + Add<Output = T> + Copy> Mul for DualScalar<T> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
@@ -92,7 +206,7 @@ impl Mul for DualScalar {
     }
 }
 
-impl MulAssign for DualScalar {
+impl<T: Mul<Output = T> + Add<Output = T> + Copy> MulAssign for DualScalar<T> {
     fn mul_assign(&mut self, rhs: Self) {
         *self = Self {
             v: self.v * rhs.v,
@@ -102,7 +216,7 @@ impl MulAssign for DualScalar {
 }
 
 // Division Rules
-impl Div for DualScalar {
+impl<T: Mul<Output = T> + Sub<Output = T> + Div<Output = T> + Copy> Div for DualScalar<T> {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
@@ -114,24 +228,24 @@ impl Div for DualScalar {
 }
 
 /// Evaluate the derivative
-pub fn derivative<F>(func: F, x0: f64) -> f64
-    where F: FnOnce(DualScalar) -> DualScalar,
+pub fn derivative<F, T: Float>(func: F, x0: T) -> T
+    where F: FnOnce(DualScalar<T>) -> DualScalar<T>,
 {
-    func(DualScalar { v: x0, dv: 1.0 }).deriv()
+    func(DualScalar { v: x0, dv: T::one() }).deriv()
 }
 
 /// Evaluate the gradient
-pub fn gradient<F>(func: F, x0: &[f64]) -> Vec<f64>
-    where F: Fn(&[DualScalar]) -> DualScalar,
+pub fn gradient<F, T: Float>(func: F, x0: &[T]) -> Vec<T>
+    where F: Fn(&[DualScalar<T>]) -> DualScalar<T>,
 {
     // To get all the partials, we set each var to have dv=1
     // and the others dv=0, and pass them through the function
-    let mut inputs: Vec<DualScalar> = x0.iter().map(|&v| DualScalar { v: v, dv: 0. }).collect();
+    let mut inputs: Vec<DualScalar<T>> = x0.iter().map(|&v| DualScalar { v: v, dv: T::zero() }).collect();
     (0..x0.len()).map(
         |i| {
-            inputs[i].dv = 1.;
+            inputs[i].dv = T::one();
             let partial = func(&inputs).deriv();
-            inputs[i].dv = 0.;
+            inputs[i].dv = T::zero();
             partial
         }
     ).collect()
@@ -139,24 +253,144 @@ pub fn gradient<F>(func: F, x0: &[f64]) -> Vec<f64>
 
 /// Evaluate the Jacobian of function f: R^N -> R^M
 /// The Jacobian will be a M-by-N matrix
-pub fn jacobian<F, const N: usize, const M: usize>(func: F, x0: &[f64]) -> SMatrix<f64, M, N>
-    where F: Fn(&[DualScalar]) -> Vec<DualScalar>,
+pub fn jacobian<F, T: Float + Debug + 'static, const N: usize, const M: usize>(func: F, x0: &[T]) -> SMatrix<T, M, N>
+    where F: Fn(&[DualScalar<T>]) -> Vec<DualScalar<T>>,
 {
     // To get all the partials, we set each var to have dv=1
     // and the others dv=0, and pass them through the function
-    let mut jacobian: SMatrix<f64, M, N> = SMatrix::zeros();
-    let mut inputs: Vec<DualScalar> = x0.iter().map(|&v| DualScalar { v: v, dv: 0. }).collect();
+    let mut jacobian: SMatrix<T, M, N> = SMatrix::zeros();
+    let mut inputs: Vec<DualScalar<T>> = x0.iter().map(|&v| DualScalar { v: v, dv: T::zero() }).collect();
 
     // every time we call the func we can get one column of partials
     for (i, mut col) in jacobian.column_iter_mut().enumerate() {
-        inputs[i].dv = 1.;
+        inputs[i].dv = T::one();
         let col_result = func(&inputs);
         for j in 0..M {
             col[j] = col_result[j].dv;
         }
-        inputs[i].dv = 0.;
+        inputs[i].dv = T::zero();
+    }
+
+    return jacobian;
+}
+
+/// Evaluate the Hessian of function f: R^N -> R via nested dual numbers.
+///
+/// Each entry `(i, j)` is obtained from a single evaluation of `func` over
+/// `DualScalar<DualScalar<f64>>` inputs, seeded with the outer epsilon on
+/// index `i` and the inner epsilon on index `j`; the second partial falls
+/// out of `value.dv.dv`.
+pub fn hessian<F, const N: usize>(func: F, x0: &[f64]) -> SMatrix<f64, N, N>
+    where F: Fn(&[DualScalar<DualScalar<f64>>]) -> DualScalar<DualScalar<f64>>,
+{
+    let mut hessian: SMatrix<f64, N, N> = SMatrix::zeros();
+
+    for i in 0..N {
+        for j in 0..N {
+            let inputs: Vec<DualScalar<DualScalar<f64>>> = x0.iter().enumerate().map(
+                |(k, &v)| {
+                    let inner_eps = if k == j { 1.0 } else { 0.0 };
+                    let outer_eps = if k == i { 1.0 } else { 0.0 };
+                    DualScalar {
+                        v: DualScalar { v, dv: inner_eps },
+                        dv: DualScalar { v: outer_eps, dv: 0.0 },
+                    }
+                }
+            ).collect();
+            hessian[(i, j)] = func(&inputs).dv.dv;
+        }
+    }
+
+    return hessian;
+}
+
+/// A vectorized dual number whose derivative part carries the partials
+/// with respect to every input at once, so a single evaluation of `f`
+/// yields the full gradient (or Jacobian row) instead of needing one
+/// pass per input as `DualScalar` does.
+#[derive(Clone, Copy, Debug)]
+pub struct DualVecN<const N: usize> {
+    pub v: f64,
+    pub eps: [f64; N],
+}
+
+impl<const N: usize> Add for DualVecN<N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut eps = [0.0; N];
+        for (k, e) in eps.iter_mut().enumerate() {
+            *e = self.eps[k] + rhs.eps[k];
+        }
+        DualVecN { v: self.v + rhs.v, eps }
+    }
+}
+
+impl<const N: usize> Sub for DualVecN<N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut eps = [0.0; N];
+        for (k, e) in eps.iter_mut().enumerate() {
+            *e = self.eps[k] - rhs.eps[k];
+        }
+        DualVecN { v: self.v - rhs.v, eps }
     }
+}
+
+impl<const N: usize> Mul for DualVecN<N> {
+    type Output = Self;
 
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut eps = [0.0; N];
+        for (k, e) in eps.iter_mut().enumerate() {
+            *e = self.eps[k] * rhs.v + self.v * rhs.eps[k];
+        }
+        DualVecN { v: self.v * rhs.v, eps }
+    }
+}
+
+impl<const N: usize> Div for DualVecN<N> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        let mut eps = [0.0; N];
+        for (k, e) in eps.iter_mut().enumerate() {
+            *e = (self.eps[k] * rhs.v - self.v * rhs.eps[k]) / (rhs.v * rhs.v);
+        }
+        DualVecN { v: self.v / rhs.v, eps }
+    }
+}
+
+/// Seed the inputs of a `DualVecN` evaluation with the identity matrix,
+/// one unit vector per input.
+fn seed_vec<const N: usize>(x0: &[f64]) -> Vec<DualVecN<N>> {
+    x0.iter().enumerate().map(
+        |(i, &v)| {
+            let mut eps = [0.0; N];
+            eps[i] = 1.0;
+            DualVecN { v, eps }
+        }
+    ).collect()
+}
+
+/// Evaluate the gradient of f: R^N -> R in a single pass
+pub fn gradient_vec<F, const N: usize>(func: F, x0: &[f64]) -> Vec<f64>
+    where F: Fn(&[DualVecN<N>]) -> DualVecN<N>,
+{
+    func(&seed_vec(x0)).eps.to_vec()
+}
+
+/// Evaluate the Jacobian of f: R^N -> R^M in a single pass
+/// The Jacobian will be a M-by-N matrix
+pub fn jacobian_vec<F, const N: usize, const M: usize>(func: F, x0: &[f64]) -> SMatrix<f64, M, N>
+    where F: Fn(&[DualVecN<N>]) -> Vec<DualVecN<N>>,
+{
+    let outputs = func(&seed_vec(x0));
+    let mut jacobian: SMatrix<f64, M, N> = SMatrix::zeros();
+    for (i, mut row) in jacobian.row_iter_mut().enumerate() {
+        row.copy_from_slice(&outputs[i].eps);
+    }
     return jacobian;
 }
 
@@ -191,4 +425,84 @@ mod tests {
         assert!(approx_eq!(f64, f_result[(1,0)], 1.0, ulps=5));
         assert!(approx_eq!(f64, f_result[(1,1)], 1.0, ulps=5));
     }
+
+    #[test]
+    fn elementary_functions_test() {
+        let f_sqrt = |x: DualScalar| x.sqrt();
+        assert!(approx_eq!(f64, derivative(f_sqrt, 4.0), 1.0 / 4.0, ulps=5));
+
+        let f_exp = |x: DualScalar| x.exp();
+        assert!(approx_eq!(f64, derivative(f_exp, 0.0), 1.0, ulps=5));
+
+        let f_ln = |x: DualScalar| x.ln();
+        assert!(approx_eq!(f64, derivative(f_ln, 2.0), 0.5, ulps=5));
+
+        let f_log2 = |x: DualScalar| x.log(2.0);
+        assert!(approx_eq!(f64, derivative(f_log2, 2.0), 1.0 / (2.0 * 2.0_f64.ln()), ulps=5));
+
+        let f_powf = |x: DualScalar| x.powf(3.0);
+        assert!(approx_eq!(f64, derivative(f_powf, 2.0), 12.0, ulps=5));
+
+        let f_powi = |x: DualScalar| x.powi(3);
+        assert!(approx_eq!(f64, derivative(f_powi, 2.0), 12.0, ulps=5));
+
+        let f_abs = |x: DualScalar| x.abs();
+        assert!(approx_eq!(f64, derivative(f_abs, -3.0), -1.0, ulps=5));
+
+        let f_sin = |x: DualScalar| x.sin();
+        assert!(approx_eq!(f64, derivative(f_sin, 0.0), 1.0, ulps=5));
+
+        let f_cos = |x: DualScalar| x.cos();
+        assert!(approx_eq!(f64, derivative(f_cos, 0.0), 0.0, ulps=5));
+
+        let f_tan = |x: DualScalar| x.tan();
+        assert!(approx_eq!(f64, derivative(f_tan, 0.0), 1.0, ulps=5));
+
+        let f_sinh = |x: DualScalar| x.sinh();
+        assert!(approx_eq!(f64, derivative(f_sinh, 0.0), 1.0, ulps=5));
+
+        let f_cosh = |x: DualScalar| x.cosh();
+        assert!(approx_eq!(f64, derivative(f_cosh, 0.0), 0.0, ulps=5));
+
+        let f_tanh = |x: DualScalar| x.tanh();
+        assert!(approx_eq!(f64, derivative(f_tanh, 0.0), 1.0, ulps=5));
+    }
+
+    #[test]
+    fn hessian_test() {
+        // f(x, y) = x^2 * y has Hessian [[2y, 2x], [2x, 0]]
+        let f_test = |x: &[DualScalar<DualScalar<f64>>]| x[0] * x[0] * x[1];
+        let h_result: SMatrix<f64, 2, 2> = hessian(f_test, vec![1., 2.].as_slice());
+        assert!(approx_eq!(f64, h_result[(0,0)], 4.0, ulps=5));
+        assert!(approx_eq!(f64, h_result[(0,1)], 2.0, ulps=5));
+        assert!(approx_eq!(f64, h_result[(1,0)], 2.0, ulps=5));
+        assert!(approx_eq!(f64, h_result[(1,1)], 0.0, ulps=5));
+    }
+
+    #[test]
+    fn gradient_vec_test() {
+        let f_test = |x: &[DualVecN<2>]| x[0] * x[1] + x[1] * x[1];
+        let f_result: Vec<f64> = gradient_vec(f_test, vec![1., 2.].as_slice());
+        assert!(approx_eq!(f64, f_result[0], 2.0, ulps=5));
+        assert!(approx_eq!(f64, f_result[1], 5.0, ulps=5));
+    }
+
+    #[test]
+    fn f32_precision_test() {
+        let f_test = |x: DualScalar<f32>| x * x + x.sin();
+        let f_result: f32 = derivative(f_test, 2.0f32);
+        assert!(approx_eq!(f32, f_result, 4.0 + 2.0f32.cos(), ulps=5));
+    }
+
+    #[test]
+    fn jacobian_vec_test() {
+        let f_test = |x: &[DualVecN<2>]| {
+            vec![x[0] * x[0] * x[1], x[0] + x[1]]
+        };
+        let f_result: SMatrix<f64, 2, 2> = jacobian_vec(f_test, vec![1., 2.].as_slice());
+        assert!(approx_eq!(f64, f_result[(0,0)], 4.0, ulps=5));
+        assert!(approx_eq!(f64, f_result[(0,1)], 1.0, ulps=5));
+        assert!(approx_eq!(f64, f_result[(1,0)], 1.0, ulps=5));
+        assert!(approx_eq!(f64, f_result[(1,1)], 1.0, ulps=5));
+    }
 }
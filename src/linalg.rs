@@ -0,0 +1,239 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::forward::DualScalar;
+use crate::reverse::Var;
+
+/// Error returned by `lu` when the matrix has no usable pivot, i.e. it is
+/// (numerically) singular.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingularMatrixError;
+
+impl Display for SingularMatrixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "matrix is singular: no pivot with non-negligible real part")
+    }
+}
+
+impl std::error::Error for SingularMatrixError {}
+
+/// Below-diagonal tolerance under which a pivot's real part is treated as zero.
+const PIVOT_TOLERANCE: f64 = 1e-12;
+
+/// A differentiable scalar that `lu`/`solve` can factor and solve over.
+/// Implemented for both forward-mode `DualScalar` and reverse-mode `Var`,
+/// so sensitivities of the solution to `A x = b` can be recovered from
+/// either mode. `real` exposes only the non-derivative part, used solely
+/// to choose pivots so the comparison itself is never differentiated.
+pub trait DiffScalar:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    fn real(&self) -> f64;
+}
+
+impl DiffScalar for DualScalar<f64> {
+    fn real(&self) -> f64 {
+        self.v
+    }
+}
+
+impl<'t> DiffScalar for Var<'t, f64> {
+    fn real(&self) -> f64 {
+        self.v
+    }
+}
+
+/// LU decomposition of a square matrix with differentiable entries, computed
+/// via Gaussian elimination with partial pivoting. `L` and `U` are packed
+/// into a single matrix (`L`'s unit diagonal is implicit), alongside the
+/// permutation applied to the rows during factorization.
+#[derive(Debug, Clone)]
+pub struct Lu<S> {
+    /// Packed `L` (below diagonal) and `U` (on/above diagonal), row-major
+    pub lu: Vec<Vec<S>>,
+    /// `p[i]` is the original row now sitting at row `i`
+    pub p: Vec<usize>,
+    /// Number of row swaps performed, e.g. useful for determinant sign
+    pub n_pivots: usize,
+}
+
+/// Factorize `a` into `P A = L U`, picking the pivot row by comparing the
+/// *real* magnitude of the column (`.real()`), never the derivative part, so
+/// pivoting stays deterministic under differentiation.
+pub fn lu<S: DiffScalar>(a: &[Vec<S>]) -> Result<Lu<S>, SingularMatrixError> {
+    let n = a.len();
+    assert!(a.iter().all(|row| row.len() == n), "lu: matrix must be square");
+    let mut lu = a.to_vec();
+    let mut p: Vec<usize> = (0..n).collect();
+    let mut n_pivots = 0;
+
+    for k in 0..n {
+        // partial pivoting: find the row (at or below k) with the largest
+        // real magnitude in column k
+        let mut pivot_row = k;
+        let mut pivot_mag = lu[k][k].real().abs();
+        for (i, row) in lu.iter().enumerate().skip(k + 1) {
+            let mag = row[k].real().abs();
+            if mag > pivot_mag {
+                pivot_mag = mag;
+                pivot_row = i;
+            }
+        }
+
+        if pivot_mag < PIVOT_TOLERANCE {
+            return Err(SingularMatrixError);
+        }
+
+        if pivot_row != k {
+            lu.swap(k, pivot_row);
+            p.swap(k, pivot_row);
+            n_pivots += 1;
+        }
+
+        let (top, bottom) = lu.split_at_mut(k + 1);
+        let pivot = &top[k];
+        for row in bottom.iter_mut() {
+            let multiplier = row[k] / pivot[k];
+            row[k] = multiplier;
+            for (row_j, pivot_j) in row.iter_mut().zip(pivot.iter()).skip(k + 1) {
+                *row_j = *row_j - multiplier * *pivot_j;
+            }
+        }
+    }
+
+    Ok(Lu { lu, p, n_pivots })
+}
+
+impl<S: DiffScalar> Lu<S> {
+    /// Solve `A x = b` using the factorization, via forward then back
+    /// substitution. Every step is ordinary differentiable arithmetic on
+    /// `S`, so derivatives of `x` with respect to `A` and `b` flow through
+    /// automatically.
+    pub fn solve(&self, b: &[S]) -> Vec<S> {
+        let n = self.lu.len();
+
+        // apply the row permutation recorded during factorization
+        let mut x: Vec<S> = self.p.iter().map(|&pi| b[pi]).collect();
+
+        // forward substitution: L y = P b (L has an implicit unit diagonal)
+        for i in 0..n {
+            let mut acc = x[i];
+            for (lu_j, x_j) in self.lu[i].iter().zip(x.iter()).take(i) {
+                acc = acc - *lu_j * *x_j;
+            }
+            x[i] = acc;
+        }
+
+        // back substitution: U x = y
+        for i in (0..n).rev() {
+            let mut acc = x[i];
+            for (lu_j, x_j) in self.lu[i].iter().zip(x.iter()).skip(i + 1) {
+                acc = acc - *lu_j * *x_j;
+            }
+            x[i] = acc / self.lu[i][i];
+        }
+
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::*;
+    use crate::reverse::Tape;
+
+    fn dual(v: f64) -> DualScalar {
+        DualScalar { v, dv: 0.0 }
+    }
+
+    #[test]
+    fn solve_2x2() {
+        // [2 1] [x]   [5]
+        // [1 3] [y] = [10]
+        // x = 1, y = 3
+        let a = vec![
+            vec![dual(2.0), dual(1.0)],
+            vec![dual(1.0), dual(3.0)],
+        ];
+        let b = vec![dual(5.0), dual(10.0)];
+
+        let factorization = lu(&a).unwrap();
+        let x = factorization.solve(&b);
+        assert!(approx_eq!(f64, x[0].v, 1.0, ulps=5));
+        assert!(approx_eq!(f64, x[1].v, 3.0, ulps=5));
+    }
+
+    #[test]
+    fn solve_requires_pivoting() {
+        // leading entry is zero, so factorization must swap rows to proceed
+        // [0 1] [x]   [2]
+        // [1 1] [y] = [3]
+        // x = 1, y = 2
+        let a = vec![
+            vec![dual(0.0), dual(1.0)],
+            vec![dual(1.0), dual(1.0)],
+        ];
+        let b = vec![dual(2.0), dual(3.0)];
+
+        let factorization = lu(&a).unwrap();
+        let x = factorization.solve(&b);
+        assert!(approx_eq!(f64, x[0].v, 1.0, ulps=5));
+        assert!(approx_eq!(f64, x[1].v, 2.0, ulps=5));
+    }
+
+    #[test]
+    fn singular_matrix_is_an_error() {
+        let a = vec![
+            vec![dual(1.0), dual(2.0)],
+            vec![dual(2.0), dual(4.0)],
+        ];
+        assert_eq!(lu(&a).unwrap_err(), SingularMatrixError);
+    }
+
+    #[test]
+    fn solve_2x2_dual_sensitivity() {
+        // Same system as `solve_2x2`; A^-1 = [[0.6, -0.2], [-0.2, 0.4]], so
+        // seeding db0 = 1 should recover the first column of A^-1 in dv.
+        let a = vec![
+            vec![dual(2.0), dual(1.0)],
+            vec![dual(1.0), dual(3.0)],
+        ];
+        let b = vec![DualScalar { v: 5.0, dv: 1.0 }, dual(10.0)];
+
+        let factorization = lu(&a).unwrap();
+        let x = factorization.solve(&b);
+        assert!(approx_eq!(f64, x[0].v, 1.0, ulps=5));
+        assert!(approx_eq!(f64, x[1].v, 3.0, ulps=5));
+        assert!(approx_eq!(f64, x[0].dv, 0.6, epsilon=1e-9));
+        assert!(approx_eq!(f64, x[1].dv, -0.2, epsilon=1e-9));
+    }
+
+    #[test]
+    fn solve_2x2_var_sensitivity() {
+        // Same system as `solve_2x2_dual_sensitivity`, but through the
+        // reverse-mode tape: backprop() from the x[0] output recovers the first
+        // row of A^-1 (dx0/db0, dx0/db1), which equals the first column checked
+        // there because A^-1 is symmetric.
+        let tape = Tape::new();
+        let a_vars = tape.add_vars(&[2.0, 1.0, 1.0, 3.0]);
+        let b_vars = tape.add_vars(&[5.0, 10.0]);
+        let a = vec![
+            vec![a_vars[0], a_vars[1]],
+            vec![a_vars[2], a_vars[3]],
+        ];
+
+        let factorization = lu(&a).unwrap();
+        let x = factorization.solve(&b_vars);
+        assert!(approx_eq!(f64, x[0].v, 1.0, ulps=5));
+        assert!(approx_eq!(f64, x[1].v, 3.0, ulps=5));
+
+        let grad = x[0].backprop();
+        assert!(approx_eq!(f64, grad.wrt(b_vars[0]), 0.6, epsilon=1e-9));
+        assert!(approx_eq!(f64, grad.wrt(b_vars[1]), -0.2, epsilon=1e-9));
+    }
+}
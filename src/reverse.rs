@@ -1,14 +1,12 @@
-use std::borrow::{Borrow, BorrowMut};
-use std::ops::{Add, Div, Mul, Neg, Sub, Index, AddAssign, MulAssign};
-use std::cell::{Ref, RefCell};
-use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::cell::RefCell;
+use num_traits::Float;
 
 /// Node in the computation graph
 #[derive(Clone, Copy)]
-pub struct Node {
+pub struct Node<T> {
     /// partials of the two parents with respect to this node
-    pub partials: [f64; 2],
+    pub partials: [T; 2],
     /// Parents to this node on the computation graph. Parents
     /// in the sense that during forward pass, this node depends
     /// on the parents' nodes.
@@ -16,24 +14,24 @@ pub struct Node {
 }
 
 /// Tape holding the computation graph
-pub struct Tape {
-    pub nodes: RefCell<Vec<Node>>,
+pub struct Tape<T> {
+    pub nodes: RefCell<Vec<Node<T>>>,
 }
 
-impl Tape {
+impl<T: Float> Tape<T> {
     /// Create a new tape
-    pub fn new() -> Tape {
+    pub fn new() -> Tape<T> {
         Tape {
-            nodes: RefCell::new(Vec::<Node>::new())
+            nodes: RefCell::new(Vec::<Node<T>>::new())
         }
     }
 
     /// Add a new (input) variable on the tape
-    pub fn var(&self, value: f64) -> Var {
+    pub fn var(&self, value: T) -> Var<'_, T> {
         let len = self.nodes.borrow().len();
         self.nodes.borrow_mut().push(
             Node {
-                partials: [0.0, 0.0],
+                partials: [T::zero(), T::zero()],
                 // for a single (input) variable, we point the parents to itself
                 parents: [len, len],
             }
@@ -45,14 +43,19 @@ impl Tape {
         }
     }
 
+    /// Add a batch of (input) variables on the tape
+    pub fn add_vars(&self, values: &[T]) -> Vec<Var<'_, T>> {
+        values.iter().map(|&value| self.var(value)).collect()
+    }
+
     /// Add a new node to the tape, where the node represents
     /// the result from a unary operation
-    pub fn unary_op(&self, partial: f64,
-                    index: usize, new_value: f64) -> Var {
+    pub fn unary_op(&self, partial: T,
+                    index: usize, new_value: T) -> Var<'_, T> {
         let len = self.nodes.borrow().len();
         self.nodes.borrow_mut().push(
             Node {
-                partials: [partial, 0.0],
+                partials: [partial, T::zero()],
                 // only the left index matters; the right index points to itself
                 parents: [index, len],
             }
@@ -66,8 +69,8 @@ impl Tape {
 
     /// Add a new node to the tape, where the node represents
     /// the result from a binary operation
-    pub fn binary_op(&self, lhs_partial: f64, rhs_partial: f64,
-                     lhs_index: usize, rhs_index: usize, new_value: f64) -> Var {
+    pub fn binary_op(&self, lhs_partial: T, rhs_partial: T,
+                     lhs_index: usize, rhs_index: usize, new_value: T) -> Var<'_, T> {
         let len = self.nodes.borrow().len();
         self.nodes.borrow_mut().push(
             Node {
@@ -86,22 +89,90 @@ impl Tape {
 
 /// Variable for computations
 #[derive(Clone, Copy)]
-pub struct Var<'t> {
+pub struct Var<'t, T> {
     /// Pointer to the tape holding the corresponding node
-    pub tape: &'t Tape,
+    pub tape: &'t Tape<T>,
     /// Index of the node in the tape
     pub index: usize,
     /// Value
-    pub v: f64,
+    pub v: T,
 }
 
-impl Var<'_> {
+impl<T: Float> Var<'_, T> {
+    /// Square root: d/dv sqrt(v) = 1 / (2 * sqrt(v))
+    pub fn sqrt(&self) -> Self {
+        let v = self.v.sqrt();
+        self.tape.unary_op(T::one() / (v + v), self.index, v)
+    }
+
+    /// Exponential: d/dv exp(v) = exp(v)
+    pub fn exp(&self) -> Self {
+        let v = self.v.exp();
+        self.tape.unary_op(v, self.index, v)
+    }
+
+    /// Natural log: d/dv ln(v) = 1 / v
+    pub fn ln(&self) -> Self {
+        self.tape.unary_op(T::one() / self.v, self.index, self.v.ln())
+    }
+
+    /// Log with arbitrary base: d/dv log_b(v) = 1 / (v * ln(b))
+    pub fn log(&self, base: T) -> Self {
+        self.tape.unary_op(T::one() / (self.v * base.ln()), self.index, self.v.log(base))
+    }
+
+    /// Power with a real exponent: d/dv v^p = p * v^(p-1)
+    pub fn powf(&self, p: T) -> Self {
+        self.tape.unary_op(p * self.v.powf(p - T::one()), self.index, self.v.powf(p))
+    }
+
+    /// Power with an integer exponent: d/dv v^n = n * v^(n-1)
+    pub fn powi(&self, n: i32) -> Self {
+        self.tape.unary_op(T::from(n).unwrap() * self.v.powi(n - 1), self.index, self.v.powi(n))
+    }
+
+    /// Absolute value: d/dv |v| = sign(v)
+    pub fn abs(&self) -> Self {
+        self.tape.unary_op(self.v.signum(), self.index, self.v.abs())
+    }
+
+    /// Sine: d/dv sin(v) = cos(v)
+    pub fn sin(&self) -> Self {
+        self.tape.unary_op(self.v.cos(), self.index, self.v.sin())
+    }
+
+    /// Cosine: d/dv cos(v) = -sin(v)
+    pub fn cos(&self) -> Self {
+        self.tape.unary_op(-self.v.sin(), self.index, self.v.cos())
+    }
+
+    /// Tangent: d/dv tan(v) = 1 / cos(v)^2
+    pub fn tan(&self) -> Self {
+        self.tape.unary_op(T::one() / (self.v.cos() * self.v.cos()), self.index, self.v.tan())
+    }
+
+    /// Hyperbolic sine: d/dv sinh(v) = cosh(v)
+    pub fn sinh(&self) -> Self {
+        self.tape.unary_op(self.v.cosh(), self.index, self.v.sinh())
+    }
+
+    /// Hyperbolic cosine: d/dv cosh(v) = sinh(v)
+    pub fn cosh(&self) -> Self {
+        self.tape.unary_op(self.v.sinh(), self.index, self.v.cosh())
+    }
+
+    /// Hyperbolic tangent: d/dv tanh(v) = 1 - tanh(v)^2
+    pub fn tanh(&self) -> Self {
+        let v = self.v.tanh();
+        self.tape.unary_op(T::one() - v * v, self.index, v)
+    }
+
     /// Perform back propagation
-    pub fn backprop(&self) -> Grad {
+    pub fn backprop(&self) -> Grad<T> {
         // vector storing the gradients
         let tape_len = self.tape.nodes.borrow().len();
-        let mut grad = vec![0.0; tape_len];
-        grad[self.index] = 1.0;
+        let mut grad = vec![T::zero(); tape_len];
+        grad[self.index] = T::one();
 
         // iterate through the tape from back to front
         // because during forward pass, we always store new nodes at the end
@@ -112,48 +183,67 @@ impl Var<'_> {
             // increment gradient contribution to the left parent
             let lhs_dep = node.parents[0];
             let lhs_partial = node.partials[0];
-            grad[lhs_dep] += lhs_partial * grad[i];
+            grad[lhs_dep] = grad[lhs_dep] + lhs_partial * grad[i];
 
             // increment gradient contribution to the right parent
             // note that in cases of unary operations, because
             // partial was set to zero, it won't affect the computation
             let rhs_dep = node.parents[1];
             let rhs_partial = node.partials[1];
-            grad[rhs_dep] += rhs_partial * grad[i];
+            grad[rhs_dep] = grad[rhs_dep] + rhs_partial * grad[i];
         }
 
         Grad { grad }
     }
 }
 
-impl<'t> Add for Var<'t> {
+impl<'t, T: Float> Add for Var<'t, T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        self.tape.binary_op(1.0, 1.0,
+        self.tape.binary_op(T::one(), T::one(),
                             self.index, rhs.index, self.v + rhs.v)
     }
 }
 
-impl<'t> Sub for Var<'t> {
+impl<'t, T: Float> Sub for Var<'t, T> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        self.tape.binary_op(1.0, -1.0,
+        self.tape.binary_op(T::one(), -T::one(),
                             self.index, rhs.index, self.v - rhs.v)
     }
 }
 
-impl<'t> Neg for Var<'t> {
+// Mixed Var/T arithmetic: the scalar is treated as a constant with zero
+// partial, so these emit a single `unary_op` node rather than forcing the
+// caller to allocate a new tape variable for every constant.
+impl<'t, T: Float> Add<T> for Var<'t, T> {
+    type Output = Self;
+
+    fn add(self, rhs: T) -> Self::Output {
+        self.tape.unary_op(T::one(), self.index, self.v + rhs)
+    }
+}
+
+impl<'t, T: Float> Sub<T> for Var<'t, T> {
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        self.tape.unary_op(T::one(), self.index, self.v - rhs)
+    }
+}
+
+impl<'t, T: Float> Neg for Var<'t, T> {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
-        self.tape.unary_op(-1.0, self.index, - self.v)
+        self.tape.unary_op(-T::one(), self.index, - self.v)
     }
 }
 
 
-impl<'t> Mul for Var<'t> {
+impl<'t, T: Float> Mul for Var<'t, T> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
@@ -162,34 +252,89 @@ impl<'t> Mul for Var<'t> {
     }
 }
 
-impl<'t> Mul<Var<'t>> for f64 {
-    type Output = Var<'t>;
+impl<'t, T: Float> Mul<T> for Var<'t, T> {
+    type Output = Self;
 
-    fn mul(self, rhs: Var<'t>) -> Self::Output {
-        rhs.tape.unary_op(self, rhs.index, self * rhs.v)
+    fn mul(self, rhs: T) -> Self::Output {
+        self.tape.unary_op(rhs, self.index, self.v * rhs)
     }
 }
 
-impl<'t> Div for Var<'t> {
+impl<'t, T: Float> Div for Var<'t, T> {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        self.tape.binary_op(1.0 / rhs.v, -self.v / (rhs.v * rhs.v),
+        self.tape.binary_op(T::one() / rhs.v, -self.v / (rhs.v * rhs.v),
                             self.index, rhs.index, self.v / rhs.v)
     }
 }
 
+impl<'t, T: Float> Div<T> for Var<'t, T> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        self.tape.unary_op(T::one() / rhs, self.index, self.v / rhs)
+    }
+}
+
+// Orphan rules require the scalar-on-the-left impls to name a concrete type,
+// so the constant-folding logic above can't be made generic over `T: Float`
+// directly; this macro instantiates it for the two `Float` types the crate
+// actually ships (`f32`, `f64`) instead of leaving `f32` silently unsupported.
+macro_rules! impl_scalar_on_left {
+    ($($t:ty),*) => {$(
+        impl<'t> Add<Var<'t, $t>> for $t {
+            type Output = Var<'t, $t>;
+
+            fn add(self, rhs: Var<'t, $t>) -> Self::Output {
+                rhs.tape.unary_op(1.0 as $t, rhs.index, self + rhs.v)
+            }
+        }
+
+        impl<'t> Sub<Var<'t, $t>> for $t {
+            type Output = Var<'t, $t>;
+
+            fn sub(self, rhs: Var<'t, $t>) -> Self::Output {
+                rhs.tape.unary_op(-1.0 as $t, rhs.index, self - rhs.v)
+            }
+        }
+
+        impl<'t> Mul<Var<'t, $t>> for $t {
+            type Output = Var<'t, $t>;
+
+            fn mul(self, rhs: Var<'t, $t>) -> Self::Output {
+                rhs.tape.unary_op(self, rhs.index, self * rhs.v)
+            }
+        }
+
+        impl<'t> Div<Var<'t, $t>> for $t {
+            type Output = Var<'t, $t>;
+
+            fn div(self, rhs: Var<'t, $t>) -> Self::Output {
+                rhs.tape.unary_op(-self / (rhs.v * rhs.v), rhs.index, self / rhs.v)
+            }
+        }
+    )*};
+}
+
+impl_scalar_on_left!(f32, f64);
+
 /// Struct holding gradients
 #[derive(Debug)]
-pub struct Grad {
-    pub grad: Vec<f64>,
+pub struct Grad<T> {
+    pub grad: Vec<T>,
 }
 
-impl Grad {
+impl<T: Copy> Grad<T> {
     /// Get the gradient with respect to a variable
-    pub fn wrt(&self, var: Var) -> f64 {
+    pub fn wrt(&self, var: Var<T>) -> T {
         self.grad[var.index]
     }
+
+    /// Get the gradient with respect to a slice of variables
+    pub fn wrt_slice(&self, vars: &[Var<T>]) -> Vec<T> {
+        vars.iter().map(|&var| self.wrt(var)).collect()
+    }
 }
 
 #[cfg(test)]
@@ -230,11 +375,147 @@ mod tests {
 
     #[test]
     fn multiple_multiplications() {
-        let tape = Tape::new();
+        let tape: Tape<f64> = Tape::new();
         let x = tape.var(1.0);
         let y = tape.var(1.0);
         let z = -2.0 * x + x * x * x * y;
         let grad = z.backprop();
         assert!(approx_eq!(f64, grad.wrt(x), 1.0, ulps=5));
     }
+
+    #[test]
+    fn elementary_functions() {
+        let tape = Tape::new();
+
+        let x = tape.var(4.0);
+        let grad = x.sqrt().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 1.0 / 4.0, ulps=5));
+
+        let x = tape.var(0.0);
+        let grad = x.exp().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 1.0, ulps=5));
+
+        let x = tape.var(2.0);
+        let grad = x.ln().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 0.5, ulps=5));
+
+        let x = tape.var(2.0);
+        let grad = x.log(2.0).backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 1.0 / (2.0 * 2.0_f64.ln()), ulps=5));
+
+        let x = tape.var(2.0);
+        let grad = x.powf(3.0).backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 12.0, ulps=5));
+
+        let x = tape.var(2.0);
+        let grad = x.powi(3).backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 12.0, ulps=5));
+
+        let x = tape.var(-3.0);
+        let grad = x.abs().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), -1.0, ulps=5));
+
+        let x = tape.var(0.0);
+        let grad = x.sin().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 1.0, ulps=5));
+
+        let x = tape.var(0.0);
+        let grad = x.cos().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 0.0, ulps=5));
+
+        let x = tape.var(0.0);
+        let grad = x.tan().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 1.0, ulps=5));
+
+        let x = tape.var(0.0);
+        let grad = x.sinh().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 1.0, ulps=5));
+
+        let x = tape.var(0.0);
+        let grad = x.cosh().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 0.0, ulps=5));
+
+        let x = tape.var(0.0);
+        let grad = x.tanh().backprop();
+        assert!(approx_eq!(f64, grad.wrt(x), 1.0, ulps=5));
+    }
+
+    #[test]
+    fn add_vars_and_wrt_slice() {
+        let tape = Tape::new();
+        let vars = tape.add_vars(&[1.0, 2.0, 3.0]);
+        let z = vars[0] + vars[1] * vars[2];
+        let grad = z.backprop();
+        let grads = grad.wrt_slice(&vars);
+        assert!(approx_eq!(f64, grads[0], 1.0, ulps=5));
+        assert!(approx_eq!(f64, grads[1], 3.0, ulps=5));
+        assert!(approx_eq!(f64, grads[2], 2.0, ulps=5));
+    }
+
+    #[test]
+    fn mixed_var_f64_arithmetic() {
+        let tape = Tape::new();
+        let x = tape.var(2.0);
+
+        let z = x + 3.0;
+        assert!(approx_eq!(f64, z.v, 5.0, ulps=5));
+        assert!(approx_eq!(f64, z.backprop().wrt(x), 1.0, ulps=5));
+
+        let z = 3.0 + x;
+        assert!(approx_eq!(f64, z.v, 5.0, ulps=5));
+        assert!(approx_eq!(f64, z.backprop().wrt(x), 1.0, ulps=5));
+
+        let z = x - 3.0;
+        assert!(approx_eq!(f64, z.v, -1.0, ulps=5));
+        assert!(approx_eq!(f64, z.backprop().wrt(x), 1.0, ulps=5));
+
+        let z = 3.0 - x;
+        assert!(approx_eq!(f64, z.v, 1.0, ulps=5));
+        assert!(approx_eq!(f64, z.backprop().wrt(x), -1.0, ulps=5));
+
+        let z = x * 3.0;
+        assert!(approx_eq!(f64, z.v, 6.0, ulps=5));
+        assert!(approx_eq!(f64, z.backprop().wrt(x), 3.0, ulps=5));
+
+        let z = x / 4.0;
+        assert!(approx_eq!(f64, z.v, 0.5, ulps=5));
+        assert!(approx_eq!(f64, z.backprop().wrt(x), 0.25, ulps=5));
+
+        let z = 4.0 / x;
+        assert!(approx_eq!(f64, z.v, 2.0, ulps=5));
+        assert!(approx_eq!(f64, z.backprop().wrt(x), -1.0, ulps=5));
+    }
+
+    #[test]
+    fn f32_precision() {
+        let tape: Tape<f32> = Tape::new();
+        let x = tape.var(2.0f32);
+        let y = tape.var(3.0f32);
+        let z = x * x * y;
+        let grad = z.backprop();
+        assert!(approx_eq!(f32, grad.wrt(x), 12.0, ulps=5));
+        assert!(approx_eq!(f32, grad.wrt(y), 4.0, ulps=5));
+    }
+
+    #[test]
+    fn f32_scalar_on_left_arithmetic() {
+        let tape: Tape<f32> = Tape::new();
+        let x = tape.var(2.0f32);
+
+        let z = 3.0f32 + x;
+        assert!(approx_eq!(f32, z.v, 5.0, ulps=5));
+        assert!(approx_eq!(f32, z.backprop().wrt(x), 1.0, ulps=5));
+
+        let z = 3.0f32 - x;
+        assert!(approx_eq!(f32, z.v, 1.0, ulps=5));
+        assert!(approx_eq!(f32, z.backprop().wrt(x), -1.0, ulps=5));
+
+        let z = 3.0f32 * x;
+        assert!(approx_eq!(f32, z.v, 6.0, ulps=5));
+        assert!(approx_eq!(f32, z.backprop().wrt(x), 3.0, ulps=5));
+
+        let z = 4.0f32 / x;
+        assert!(approx_eq!(f32, z.v, 2.0, ulps=5));
+        assert!(approx_eq!(f32, z.backprop().wrt(x), -1.0, ulps=5));
+    }
 }